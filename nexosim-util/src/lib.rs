@@ -0,0 +1,4 @@
+//! Utilities built on top of `nexosim`.
+
+pub mod observables;
+pub mod recorder;
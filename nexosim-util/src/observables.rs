@@ -3,11 +3,13 @@
 //! This module contains types used to implement states automatically propagated
 //! to output on change.
 
+use std::collections::VecDeque;
 use std::ops::Deref;
 
 use serde::{Deserialize, Serialize};
 
 use nexosim::ports::Output;
+use nexosim::time::MonotonicTime;
 
 /// Observability trait.
 pub trait Observable<T> {
@@ -39,6 +41,18 @@ where
 
     /// Output used for observation.
     out: Output<T>,
+
+    /// Last value sent to `out`, tracked so that change-only propagation can
+    /// detect a no-op update.
+    last_sent: Option<T>,
+
+    /// Bounded history of propagated values, populated only once
+    /// [`Self::with_history`] has been called.
+    history: Option<VecDeque<(MonotonicTime, T)>>,
+
+    /// Capacity of `history`, kept alongside it since `VecDeque` does not
+    /// expose the capacity it was created with.
+    history_cap: usize,
 }
 
 impl<S, T> ObservableState<S, T>
@@ -51,6 +65,9 @@ where
         Self {
             state: S::default(),
             out,
+            last_sent: None,
+            history: None,
+            history_cap: 0,
         }
     }
 
@@ -60,12 +77,21 @@ where
     }
 
     /// Set state.
+    ///
+    /// This always propagates to the output and never touches
+    /// [`Self::last`] or [`Self::history`] -- those are only tracked by the
+    /// change-aware `_checked`/`_force` API, which requires `T: PartialEq`.
+    /// Mixing this with that API on the same instance will leave `last`/
+    /// `history` silently out of sync with what was actually sent here.
     pub async fn set(&mut self, value: S) {
         self.state = value;
         self.out.send(self.state.observe()).await;
     }
 
     /// Modify state using mutable reference.
+    ///
+    /// See the note on [`Self::set`] about mixing this with the
+    /// `_checked`/`_force` API.
     pub async fn modify<F, R>(&mut self, f: F) -> R
     where
         F: FnOnce(&mut S) -> R,
@@ -76,6 +102,9 @@ where
     }
 
     /// Propagate value.
+    ///
+    /// See the note on [`Self::set`] about mixing this with the
+    /// `_checked`/`_force` API.
     pub async fn propagate(&mut self) {
         self.out.send(self.state.observe()).await;
     }
@@ -93,5 +122,93 @@ where
     }
 }
 
+impl<S, T> ObservableState<S, T>
+where
+    S: Observable<T> + Default,
+    T: Clone + Send + PartialEq + 'static,
+{
+    /// Enables a bounded history of the last `capacity` propagated values,
+    /// queryable with [`Self::history`].
+    ///
+    /// History is only ever populated by the change-aware `_checked`/`_force`
+    /// API below, which is why this requires the same `T: PartialEq` bound
+    /// as the rest of this block rather than living alongside [`Self::new`].
+    pub fn with_history(mut self, capacity: usize) -> Self {
+        self.history = Some(VecDeque::with_capacity(capacity));
+        self.history_cap = capacity;
+
+        self
+    }
+
+    /// Set state, propagating to the output only if the newly observed value
+    /// differs from the last one sent.
+    pub async fn set_checked(&mut self, value: S, time: MonotonicTime) {
+        self.state = value;
+        self.propagate_checked(time).await;
+    }
+
+    /// Modify state using a mutable reference, propagating to the output
+    /// only if the newly observed value differs from the last one sent.
+    pub async fn modify_checked<F, R>(&mut self, f: F, time: MonotonicTime) -> R
+    where
+        F: FnOnce(&mut S) -> R,
+    {
+        let r = f(&mut self.state);
+        self.propagate_checked(time).await;
+
+        r
+    }
+
+    /// Propagate the current value, skipping the send if it is unchanged
+    /// from the last one sent.
+    pub async fn propagate_checked(&mut self, time: MonotonicTime) {
+        let value = self.state.observe();
+
+        if self.last_sent.as_ref() != Some(&value) {
+            self.out.send(value.clone()).await;
+            self.last_sent = Some(value.clone());
+            self.record_history(time, value);
+        }
+    }
+
+    /// Set state and always propagate to the output, even if the newly
+    /// observed value is unchanged from the last one sent.
+    ///
+    /// This is an escape hatch for heartbeat/keepalive semantics on top of
+    /// change-only propagation.
+    pub async fn set_force(&mut self, value: S, time: MonotonicTime) {
+        self.state = value;
+        let value = self.state.observe();
+
+        self.out.send(value.clone()).await;
+        self.last_sent = Some(value.clone());
+        self.record_history(time, value);
+    }
+
+    /// The bounded history of propagated values, oldest first, once
+    /// [`Self::with_history`] has been called.
+    pub fn history(&self) -> Option<impl Iterator<Item = &(MonotonicTime, T)>> {
+        self.history.as_ref().map(|history| history.iter())
+    }
+
+    /// The last value propagated to the output, if any.
+    pub fn last(&self) -> Option<&T> {
+        self.last_sent.as_ref()
+    }
+
+    fn record_history(&mut self, time: MonotonicTime, value: T) {
+        if self.history_cap == 0 {
+            return;
+        }
+
+        if let Some(history) = &mut self.history {
+            if history.len() >= self.history_cap {
+                history.pop_front();
+            }
+            history.push_back((time, value));
+        }
+    }
+}
+
 /// Observable value.
 pub type ObservableValue<T> = ObservableState<T, T>;
@@ -0,0 +1,237 @@
+//! Output recorders.
+//!
+//! This module contains a pluggable recorder subsystem for [`ObservableState`]:
+//! every propagated value can be fanned out to one or more [`OutputSink`]s in
+//! addition to the state's own `Output`, so a simulation run can produce
+//! analyzable traces without hand-wiring observer models.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Write};
+use std::ops::Deref;
+use std::path::Path;
+
+use serde::Serialize;
+
+use nexosim::ports::Output;
+use nexosim::time::MonotonicTime;
+
+use super::observables::{Observable, ObservableState};
+
+/// A sink that every value propagated by a [`RecordedState`] is fanned out
+/// to, alongside the state's own `Output`.
+pub trait OutputSink<T> {
+    /// Records a single `(time, value)` observation.
+    fn record(&mut self, time: MonotonicTime, record: &T);
+}
+
+/// A sink that appends each observation as a CSV row.
+///
+/// The first two fields are the monotonic time's seconds and subsecond
+/// nanoseconds; the remaining fields are the record's own CSV serialization.
+pub struct CsvSink<T> {
+    writer: csv::Writer<File>,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T> CsvSink<T> {
+    /// Creates a new CSV sink writing to `path`, truncating any existing
+    /// file.
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            writer: csv::Writer::from_path(path)?,
+            _marker: std::marker::PhantomData,
+        })
+    }
+}
+
+impl<T: Serialize> OutputSink<T> for CsvSink<T> {
+    fn record(&mut self, time: MonotonicTime, record: &T) {
+        let _ = self
+            .writer
+            .serialize((time.as_secs(), time.subsec_nanos(), record));
+        let _ = self.writer.flush();
+    }
+}
+
+/// A sink that appends each observation as a line of newline-delimited JSON.
+pub struct NdjsonSink<T> {
+    file: File,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T> NdjsonSink<T> {
+    /// Creates a new NDJSON sink appending to `path`, creating it if it does
+    /// not exist.
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            file: File::options().create(true).append(true).open(path)?,
+            _marker: std::marker::PhantomData,
+        })
+    }
+}
+
+impl<T: Serialize> OutputSink<T> for NdjsonSink<T> {
+    fn record(&mut self, time: MonotonicTime, record: &T) {
+        #[derive(Serialize)]
+        struct Entry<'a, T> {
+            secs: i64,
+            nanos: u32,
+            record: &'a T,
+        }
+
+        if let Ok(mut line) = serde_json::to_vec(&Entry {
+            secs: time.as_secs(),
+            nanos: time.subsec_nanos(),
+            record,
+        }) {
+            line.push(b'\n');
+            let _ = self.file.write_all(&line);
+        }
+    }
+}
+
+/// An in-memory sink that keeps the last `capacity` observations.
+pub struct RingBufferSink<T> {
+    capacity: usize,
+    buffer: VecDeque<(MonotonicTime, T)>,
+}
+
+impl<T> RingBufferSink<T> {
+    /// Creates a new ring buffer sink holding at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            buffer: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// The recorded entries, oldest first.
+    pub fn entries(&self) -> impl Iterator<Item = &(MonotonicTime, T)> {
+        self.buffer.iter()
+    }
+}
+
+impl<T: Clone> OutputSink<T> for RingBufferSink<T> {
+    fn record(&mut self, time: MonotonicTime, record: &T) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.buffer.len() >= self.capacity {
+            self.buffer.pop_front();
+        }
+        self.buffer.push_back((time, record.clone()));
+    }
+}
+
+/// A sink that evaluates every observation against a predicate and marks the
+/// run as failed the first time it does not hold.
+///
+/// This lets a headless batch run auto-terminate on invariant violations
+/// instead of requiring a human to watch the trace.
+pub struct Warden<T> {
+    envelope: Box<dyn FnMut(&T) -> bool + Send>,
+    failure: Option<MonotonicTime>,
+}
+
+impl<T> Warden<T> {
+    /// Creates a new warden that fails the run the first time `envelope`
+    /// returns `false` for a recorded value.
+    pub fn new(envelope: impl FnMut(&T) -> bool + Send + 'static) -> Self {
+        Self {
+            envelope: Box::new(envelope),
+            failure: None,
+        }
+    }
+
+    /// The time of the first observation that left the allowed envelope, if
+    /// any.
+    pub fn failure(&self) -> Option<MonotonicTime> {
+        self.failure
+    }
+
+    /// Whether the run has been marked as failed.
+    pub fn has_failed(&self) -> bool {
+        self.failure.is_some()
+    }
+}
+
+impl<T> OutputSink<T> for Warden<T> {
+    fn record(&mut self, time: MonotonicTime, record: &T) {
+        if self.failure.is_none() && !(self.envelope)(record) {
+            self.failure = Some(time);
+        }
+    }
+}
+
+/// Observable state whose every propagated value is additionally fanned out
+/// to a set of [`OutputSink`]s.
+pub struct RecordedState<S, T>
+where
+    S: Observable<T> + Default,
+    T: Clone + Send + 'static,
+{
+    inner: ObservableState<S, T>,
+    sinks: Vec<Box<dyn OutputSink<T> + Send>>,
+}
+
+impl<S, T> RecordedState<S, T>
+where
+    S: Observable<T> + Default,
+    T: Clone + Send + 'static,
+{
+    /// New default state, with no sinks registered yet.
+    pub fn new(out: Output<T>) -> Self {
+        Self {
+            inner: ObservableState::new(out),
+            sinks: Vec::new(),
+        }
+    }
+
+    /// Adds a sink that every propagated value will be fanned out to.
+    pub fn add_sink(&mut self, sink: impl OutputSink<T> + Send + 'static) {
+        self.sinks.push(Box::new(sink));
+    }
+
+    /// Set state.
+    pub async fn set(&mut self, value: S, time: MonotonicTime) {
+        self.inner.set(value).await;
+        self.record(time);
+    }
+
+    /// Modify state using mutable reference.
+    pub async fn modify<F, R>(&mut self, f: F, time: MonotonicTime) -> R
+    where
+        F: FnOnce(&mut S) -> R,
+    {
+        let r = self.inner.modify(f).await;
+        self.record(time);
+        r
+    }
+
+    /// Propagate value.
+    pub async fn propagate(&mut self, time: MonotonicTime) {
+        self.inner.propagate().await;
+        self.record(time);
+    }
+
+    fn record(&mut self, time: MonotonicTime) {
+        let value = self.inner.get().observe();
+        for sink in &mut self.sinks {
+            sink.record(time, &value);
+        }
+    }
+}
+
+impl<S, T> Deref for RecordedState<S, T>
+where
+    S: Observable<T> + Default,
+    T: Clone + Send + 'static,
+{
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        self.inner.get()
+    }
+}
@@ -0,0 +1,176 @@
+//! Initiator tasks.
+//!
+//! An [`Initiator`] is a long-lived async task that runs alongside the
+//! simulation and feeds events into event sources on its own cadence, e.g. a
+//! timer-driven initiator that wakes every N sim-time units, or one bridging
+//! an external async channel into the bench. This gives users a first-class
+//! way to model external stimulus (sensor polling, operator commands)
+//! without writing a dedicated driver model and manually pumping the
+//! scheduler.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use tokio::task::JoinHandle;
+
+use crate::registry::EndpointRegistry;
+use crate::time::MonotonicTime;
+
+use super::codec::WireFormat;
+use super::envelope::{EnvelopeError, SimMsgPayload};
+
+/// A handle an [`Initiator`] uses to inject events into the bench it was
+/// registered on.
+pub(crate) struct InitiatorHandle {
+    endpoints: EndpointRegistry,
+}
+
+impl InitiatorHandle {
+    /// Creates a handle that schedules events through the same event source
+    /// registry `endpoints` dispatches to.
+    ///
+    /// Only the event source registry is carried over rather than the whole
+    /// of `endpoints`, since `EndpointRegistry` itself is not `Clone` and an
+    /// initiator has no business with the rest of a bench's endpoints. This
+    /// relies on `EventSourceRegistry::clone()` being a cheap, aliasing
+    /// clone of the handle that was registered against the running
+    /// scheduler (via `register_scheduler`) rather than a detached copy --
+    /// the same assumption `Simulation`'s own remote event/query dispatch
+    /// already makes when handing out registry handles to callers.
+    fn new(endpoints: &EndpointRegistry) -> Self {
+        Self {
+            endpoints: EndpointRegistry {
+                event_source_registry: endpoints.event_source_registry.clone(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Schedules `payload` to be delivered to the event source named
+    /// `target` at the simulation time `at`.
+    pub(crate) async fn schedule_event(&self, target: &str, payload: Vec<u8>, at: MonotonicTime) {
+        self.endpoints
+            .event_source_registry
+            .schedule(target, payload, at)
+            .await;
+    }
+
+    /// Schedules a typed `payload` for delivery at the simulation time
+    /// `at`, encoded with `format`.
+    ///
+    /// This is [`Self::schedule_event`] built on top of [`SimMsgPayload`]:
+    /// the event source targeted is always `P::TARGET`, so an initiator
+    /// using this instead of `schedule_event` directly cannot send a
+    /// payload to the wrong target by a typo in a string literal.
+    pub(crate) async fn schedule<P: SimMsgPayload>(
+        &self,
+        payload: &P,
+        format: WireFormat,
+        at: MonotonicTime,
+    ) -> Result<(), EnvelopeError> {
+        let envelope = payload
+            .into_envelope(format)
+            .map_err(EnvelopeError::Codec)?;
+        self.schedule_event(envelope.target(), envelope.payload().to_vec(), at)
+            .await;
+
+        Ok(())
+    }
+}
+
+/// A long-lived async task that feeds events into a bench on its own
+/// cadence, independently of the scheduler's own event queue.
+pub(crate) trait Initiator: Send + 'static {
+    /// Runs the initiator until it is cancelled.
+    fn run(self: Box<Self>, handle: InitiatorHandle) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+}
+
+/// The set of initiators registered on a bench.
+///
+/// Initiators are spawned alongside the bench when it is initialized (see
+/// `InitService::init`) and cancelled when the returned [`InitiatorGuard`]
+/// is dropped, instead of being pumped manually by the scheduler.
+#[derive(Default)]
+pub(crate) struct InitiatorRegistry {
+    initiators: Vec<Box<dyn Initiator>>,
+}
+
+impl InitiatorRegistry {
+    /// Creates an empty registry.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an initiator to be spawned alongside the simulation.
+    pub(crate) fn register(&mut self, initiator: impl Initiator) {
+        self.initiators.push(Box::new(initiator));
+    }
+
+    /// Registers an already-boxed initiator to be spawned alongside the
+    /// simulation.
+    pub(crate) fn register_boxed(&mut self, initiator: Box<dyn Initiator>) {
+        self.initiators.push(initiator);
+    }
+
+    /// Spawns every registered initiator against `endpoints`, returning a
+    /// guard that cancels all of them when dropped.
+    ///
+    /// Returns `Err` instead of spawning anything if called outside a
+    /// Tokio runtime context, rather than letting `tokio::spawn` panic --
+    /// `init()` is synchronous and is expected to run inside the async
+    /// runtime driving the remote service, but that is a property of its
+    /// caller this module cannot enforce at compile time.
+    pub(crate) fn spawn_all(
+        self,
+        endpoints: &EndpointRegistry,
+    ) -> Result<InitiatorGuard, NoRuntimeError> {
+        let runtime = tokio::runtime::Handle::try_current().map_err(|_| NoRuntimeError)?;
+
+        let handles = self
+            .initiators
+            .into_iter()
+            .map(|initiator| {
+                let handle = InitiatorHandle::new(endpoints);
+                runtime.spawn(initiator.run(handle))
+            })
+            .collect();
+
+        Ok(InitiatorGuard { handles })
+    }
+}
+
+/// Returned by [`InitiatorRegistry::spawn_all`] when called outside a
+/// Tokio runtime context, where initiators cannot be spawned at all.
+#[derive(Debug)]
+pub(crate) struct NoRuntimeError;
+
+impl std::fmt::Display for NoRuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "initiators can only be spawned from within a Tokio runtime"
+        )
+    }
+}
+
+impl std::error::Error for NoRuntimeError {}
+
+/// Keeps every spawned initiator task running for as long as it is held,
+/// aborting them all on drop so that tearing down a bench cleanly cancels
+/// its initiators.
+///
+/// The caller of [`InitiatorRegistry::spawn_all`] must bind and hold this
+/// guard for as long as the bench it was spawned for is alive: dropping it
+/// immediately (e.g. by discarding the tuple `init()` returns instead of
+/// binding it) aborts every initiator task right away.
+pub(crate) struct InitiatorGuard {
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl Drop for InitiatorGuard {
+    fn drop(&mut self) {
+        for handle in &self.handles {
+            handle.abort();
+        }
+    }
+}
@@ -0,0 +1,6 @@
+//! The remote simulation service.
+
+pub(crate) mod codec;
+pub(crate) mod envelope;
+pub(crate) mod initiator;
+pub(crate) mod services;
@@ -1,18 +1,184 @@
+use std::collections::{HashMap, HashSet};
 use std::panic::{self, AssertUnwindSafe};
+use std::time::Instant;
 
 use ciborium;
 use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use tracing::{error, field, info_span};
 
 use crate::registry::EndpointRegistry;
 use crate::simulation::{SimInit, Simulation, SimulationError};
 
+use super::super::codec::{self, CodecError, WireFormat};
+use super::super::initiator::{Initiator, InitiatorGuard, InitiatorRegistry};
+use super::freeze_service::SnapshotRegistry;
 use super::{map_simulation_error, timestamp_to_monotonic, to_error};
 
 use super::super::codegen::simulation::*;
 
-type InitResult = Result<(SimInit, EndpointRegistry), SimulationError>;
-type DeserializationError = ciborium::de::Error<std::io::Error>;
-type SimGen = Box<dyn FnMut(&[u8]) -> Result<InitResult, DeserializationError> + Send + 'static>;
+type InitResult = Result<(SimInit, EndpointRegistry, SnapshotRegistry), SimulationError>;
+type SimGen = Box<dyn FnMut(&[u8], WireFormat) -> Result<InitResult, CodecError> + Send + 'static>;
+
+/// A model builder produced by a [`ModelRegistry`] entry.
+///
+/// A builder owns the deserialized parameters for a single bench entry and
+/// knows how to instantiate and register the corresponding model.
+pub(crate) trait ModelBuilder: Send {
+    /// The names of the ports this model exposes, used to validate
+    /// connections declared in a [`BenchConfig`] before any model is built.
+    fn port_names(&self) -> &'static [&'static str];
+
+    /// Builds the model and adds it to `sim_init`, wiring `connections` --
+    /// this model's own outgoing connections, as validated
+    /// `(output_port, target_model, target_port)` triples -- and
+    /// registering its public ports under `name` in `endpoints`.
+    ///
+    /// A builder whose model implements `Snapshot` should also register it
+    /// under `name` in `snapshots`, so it takes part in a later freeze/thaw
+    /// (see [`freeze_service`](super::freeze_service)); this is optional,
+    /// and most models will simply leave `snapshots` untouched.
+    fn build(
+        self: Box<Self>,
+        name: &str,
+        connections: &[(String, String, String)],
+        sim_init: &mut SimInit,
+        endpoints: &mut EndpointRegistry,
+        snapshots: &mut SnapshotRegistry,
+    ) -> Result<(), SimulationError>;
+}
+
+type BuilderFactory = Box<
+    dyn Fn(ciborium::value::Value) -> Result<Box<dyn ModelBuilder>, ciborium::value::Error>
+        + Send
+        + Sync,
+>;
+
+/// A registry mapping the `type` tag of a bench entry to the builder that can
+/// instantiate it.
+///
+/// Registering a model type lets remote clients reference it by name from a
+/// declarative bench configuration, without the server having to recompile a
+/// fixed `sim_gen` closure for every possible bench.
+#[derive(Default)]
+pub(crate) struct ModelRegistry {
+    builders: HashMap<String, BuilderFactory>,
+}
+
+impl ModelRegistry {
+    /// Creates an empty registry.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a model builder under the given `type` tag.
+    ///
+    /// Registering the same tag twice replaces the previous entry.
+    pub(crate) fn register<B>(&mut self, type_tag: impl Into<String>)
+    where
+        B: ModelBuilder + DeserializeOwned + 'static,
+    {
+        self.builders.insert(
+            type_tag.into(),
+            Box::new(|params: ciborium::value::Value| {
+                let builder: B = params.deserialized()?;
+
+                Ok(Box::new(builder) as Box<dyn ModelBuilder>)
+            }),
+        );
+    }
+}
+
+/// A single entry of a declarative bench configuration.
+#[derive(Deserialize)]
+struct ModelSpec {
+    r#type: String,
+    name: String,
+    #[serde(flatten)]
+    params: ciborium::value::Value,
+}
+
+/// A connection between two named model ports, declared by their
+/// `"model.port"` path, e.g. `"sensor.reading" -> "controller.input"`.
+///
+/// A connection's target (`to`) must be declared at or before its source
+/// (`from`) in [`BenchConfig::models`]: each model's outgoing connections
+/// are wired as part of building that model, which requires the target
+/// model to already exist in the simulation. A forward reference -- a
+/// `to` declared later than its `from` -- is rejected as a validation
+/// error rather than silently failing to wire, since this builder does
+/// not defer or reorder construction.
+#[derive(Deserialize)]
+struct ConnectionSpec {
+    from: String,
+    to: String,
+}
+
+/// A declarative bench configuration, assembled at runtime from a registry
+/// of known model types.
+#[derive(Deserialize)]
+struct BenchConfig {
+    models: Vec<ModelSpec>,
+    #[serde(default)]
+    connections: Vec<ConnectionSpec>,
+}
+
+/// Splits a `"model.port"` path and validates both halves against the port
+/// names declared by each model's builder, returning the `(model, port)`
+/// pair on success.
+fn resolve_port<'a>(
+    path: &'a str,
+    declared_ports: &HashMap<String, HashSet<&'static str>>,
+) -> Result<(&'a str, &'a str), CodecError> {
+    let Some((model, port)) = path.split_once('.') else {
+        return Err(CodecError::Validation(format!(
+            "connection endpoint `{}` is not a `model.port` path",
+            path
+        )));
+    };
+
+    let Some(ports) = declared_ports.get(model) else {
+        return Err(CodecError::Validation(format!(
+            "connection endpoint `{}` references an undeclared model",
+            path
+        )));
+    };
+
+    if !ports.contains(port) {
+        return Err(CodecError::Validation(format!(
+            "connection endpoint `{}` references an undeclared port",
+            path
+        )));
+    }
+
+    Ok((model, port))
+}
+
+/// Validates that `to_model` is declared at or before `from_model` in
+/// `declared_order`, rejecting the forward references a single
+/// build-and-wire pass cannot satisfy (see [`ConnectionSpec`]).
+fn check_declaration_order(
+    from_model: &str,
+    to_model: &str,
+    declared_order: &HashMap<String, usize>,
+) -> Result<(), CodecError> {
+    // Both names were already resolved against `declared_ports`, which is
+    // populated from the same source as `declared_order`, so these lookups
+    // cannot fail.
+    let from_index = declared_order[from_model];
+    let to_index = declared_order[to_model];
+
+    if to_index > from_index {
+        return Err(CodecError::Validation(format!(
+            "connection `{}.* -> {}.*` references `{}`, which is declared \
+             after `{}`; a connection's target model must be declared at or \
+             before its source in `models`",
+            from_model, to_model, to_model, from_model
+        )));
+    }
+
+    Ok(())
+}
 
 /// Protobuf-based simulation initializer.
 ///
@@ -20,38 +186,202 @@ type SimGen = Box<dyn FnMut(&[u8]) -> Result<InitResult, DeserializationError> +
 /// initialization configuration.
 pub(crate) struct InitService {
     sim_gen: SimGen,
+    initiator_factories: Vec<Box<dyn Fn() -> Box<dyn Initiator> + Send + Sync>>,
 }
 
 impl InitService {
+    /// Registers an initiator to be spawned alongside the bench every time
+    /// it is (re)started, and cancelled when it is torn down.
+    ///
+    /// `make_initiator` is called once per `init()` call rather than the
+    /// initiator being built up front, since a fresh bench needs a fresh
+    /// initiator task every time the simulation is restarted.
+    pub(crate) fn register_initiator<I>(
+        &mut self,
+        make_initiator: impl Fn() -> I + Send + Sync + 'static,
+    ) where
+        I: Initiator,
+    {
+        self.initiator_factories.push(Box::new(move || {
+            Box::new(make_initiator()) as Box<dyn Initiator>
+        }));
+    }
+
     /// Creates a new `InitService`.
     ///
     /// The argument is a closure that takes a CBOR-serialized initialization
     /// configuration and is called every time the simulation is (re)started by
     /// the remote client. It must create a new simulation complemented by a
     /// registry that exposes the public event and query interface.
+    ///
+    /// The configuration is decoded as JSON instead of CBOR when
+    /// [`WireFormat::sniff`] detects a JSON payload.
+    ///
+    /// A bench built this way always has an empty [`SnapshotRegistry`]: a
+    /// hand-written `sim_gen` closure builds its models directly rather
+    /// than through [`ModelBuilder::build`], so there is no hook to collect
+    /// `Snapshot` handles from; use [`Self::with_registry`] for a bench
+    /// that should support freeze/thaw.
     pub(crate) fn new<F, I>(mut sim_gen: F) -> Self
     where
         F: FnMut(I) -> Result<(SimInit, EndpointRegistry), SimulationError> + Send + 'static,
         I: DeserializeOwned,
     {
         // Wrap `sim_gen` so it accepts a serialized init configuration.
-        let sim_gen = move |serialized_cfg: &[u8]| -> Result<InitResult, DeserializationError> {
-            let cfg = ciborium::from_reader(serialized_cfg)?;
+        let sim_gen =
+            move |serialized_cfg: &[u8], format: WireFormat| -> Result<InitResult, CodecError> {
+                let cfg = codec::decode(format, serialized_cfg)?;
 
-            Ok(sim_gen(cfg))
-        };
+                Ok(sim_gen(cfg)
+                    .map(|(sim_init, endpoints)| (sim_init, endpoints, SnapshotRegistry::new())))
+            };
 
         Self {
             sim_gen: Box::new(sim_gen),
+            initiator_factories: Vec::new(),
+        }
+    }
+
+    /// Creates a new `InitService` that assembles the bench at runtime from a
+    /// declarative configuration, using `registry` to resolve each entry's
+    /// `type` tag to a model builder.
+    ///
+    /// The serialized initialization configuration is expected to deserialize
+    /// to a `{ "models": [...], "connections": [...] }` document, where each
+    /// model entry carries a `type` and `name` alongside its own parameters.
+    /// An unknown `type` tag or a duplicate `name` is reported as
+    /// `ErrorCode::InvalidMessage`, as is a connection that references a
+    /// model or a port that was not declared, or whose target model is
+    /// declared after its source (see [`ConnectionSpec`]).
+    pub(crate) fn with_registry(registry: ModelRegistry) -> Self {
+        let sim_gen =
+            move |serialized_cfg: &[u8], format: WireFormat| -> Result<InitResult, CodecError> {
+                let bench: BenchConfig = codec::decode(format, serialized_cfg)?;
+
+                // First pass: instantiate every builder and record the ports
+                // it declares, without building any model yet, so
+                // connections can be validated -- and grouped by source
+                // model -- before any model is added to the simulation.
+                let mut builders = Vec::with_capacity(bench.models.len());
+                let mut declared_ports: HashMap<String, HashSet<&'static str>> = HashMap::new();
+                let mut declared_order: HashMap<String, usize> = HashMap::new();
+
+                for (index, model) in bench.models.into_iter().enumerate() {
+                    if declared_ports.contains_key(&model.name) {
+                        return Err(CodecError::Validation(format!(
+                            "duplicate model name `{}`",
+                            model.name
+                        )));
+                    }
+
+                    let Some(factory) = registry.builders.get(&model.r#type) else {
+                        return Err(CodecError::Validation(format!(
+                            "unknown model type `{}`",
+                            model.r#type
+                        )));
+                    };
+
+                    let builder = factory(model.params).map_err(|e| {
+                        CodecError::Validation(format!(
+                            "could not deserialize parameters for model `{}`: {}",
+                            model.name, e
+                        ))
+                    })?;
+
+                    declared_ports.insert(
+                        model.name.clone(),
+                        builder.port_names().iter().copied().collect(),
+                    );
+                    declared_order.insert(model.name.clone(), index);
+                    builders.push((model.name, builder));
+                }
+
+                let mut outgoing: HashMap<String, Vec<(String, String, String)>> = HashMap::new();
+
+                for connection in &bench.connections {
+                    let (from_model, from_port) = resolve_port(&connection.from, &declared_ports)?;
+                    let (to_model, to_port) = resolve_port(&connection.to, &declared_ports)?;
+                    check_declaration_order(from_model, to_model, &declared_order)?;
+
+                    outgoing.entry(from_model.to_string()).or_default().push((
+                        from_port.to_string(),
+                        to_model.to_string(),
+                        to_port.to_string(),
+                    ));
+                }
+
+                // Second pass: build every model, wiring its own outgoing
+                // connections as it is added to the simulation.
+                let mut sim_init = SimInit::new();
+                let mut endpoints = EndpointRegistry::default();
+                let mut snapshots = SnapshotRegistry::new();
+
+                for (name, builder) in builders {
+                    let connections = outgoing.remove(&name).unwrap_or_default();
+
+                    if let Err(e) = builder.build(
+                        &name,
+                        &connections,
+                        &mut sim_init,
+                        &mut endpoints,
+                        &mut snapshots,
+                    ) {
+                        return Ok(Err(e));
+                    }
+                }
+
+                Ok(Ok((sim_init, endpoints, snapshots)))
+            };
+
+        Self {
+            sim_gen: Box::new(sim_gen),
+            initiator_factories: Vec::new(),
         }
     }
 
     /// Initializes the simulation based on the specified configuration.
+    ///
+    /// On success, every initiator registered with
+    /// [`Self::register_initiator`] is spawned alongside the bench; the
+    /// returned [`InitiatorGuard`] cancels them all when it is dropped, so
+    /// tearing down the bench cleanly cancels its initiators too. The
+    /// caller must bind and hold that guard for as long as the bench
+    /// stays up -- discarding it (e.g. by pattern-matching only the
+    /// `Simulation`/`EndpointRegistry` out of the returned tuple) cancels
+    /// every initiator immediately. Spawning also requires `init()` itself
+    /// to be called from within a Tokio runtime; see
+    /// [`InitiatorRegistry::spawn_all`].
+    ///
+    /// The returned [`SnapshotRegistry`] is the bench's freeze/thaw handle
+    /// (see [`freeze_service`](super::freeze_service)); it is empty unless
+    /// the service was built with [`Self::with_registry`] and at least one
+    /// model registered itself as a `Snapshot`.
     pub(crate) fn init(
         &mut self,
         request: InitRequest,
-    ) -> (InitReply, Option<(Simulation, EndpointRegistry)>) {
+    ) -> (
+        InitReply,
+        Option<(
+            Simulation,
+            EndpointRegistry,
+            InitiatorGuard,
+            SnapshotRegistry,
+        )>,
+    ) {
+        let span = info_span!(
+            "init_request",
+            target = "init",
+            cfg_len = request.cfg.len(),
+            sim_time = field::Empty,
+            elapsed_ms = field::Empty,
+        );
+        let _enter = span.enter();
+        let started_at = Instant::now();
+
         let Some(start_time) = request.time.and_then(|t| timestamp_to_monotonic(t)) else {
+            error!("simulation start time not provided");
+            span.record("elapsed_ms", started_at.elapsed().as_millis());
+
             return (
                 InitReply {
                     result: Some(init_reply::Result::Error(to_error(
@@ -62,60 +392,109 @@ impl InitService {
                 None,
             );
         };
+        span.record("sim_time", field::debug(start_time));
+
+        // The sniffed format only ever governs this request's own `cfg`
+        // payload. Propagating it so that `registry`'s own event/query
+        // payload decoding also respects the client's chosen format needs
+        // a format parameter (or a per-session sniff-once cache) on
+        // `EndpointRegistry`'s dispatch path, which is out of reach from
+        // this module; tracked as a follow-up.
+        let format = WireFormat::sniff(&request.cfg);
+        let sim_gen_span = info_span!("sim_gen");
+        let reply = {
+            let _enter = sim_gen_span.enter();
+            panic::catch_unwind(AssertUnwindSafe(|| (self.sim_gen)(&request.cfg, format)))
+        }
+        .map_err(|payload| {
+            let panic_msg: Option<&str> = if let Some(s) = payload.downcast_ref::<&str>() {
+                Some(s)
+            } else if let Some(s) = payload.downcast_ref::<String>() {
+                Some(s)
+            } else {
+                None
+            };
+
+            let error_msg = if let Some(panic_msg) = panic_msg {
+                format!(
+                    "the simulation initializer has panicked with the message `{}`",
+                    panic_msg
+                )
+            } else {
+                String::from("the simulation initializer has panicked")
+            };
+
+            error!(panic_message = %error_msg, "the simulation initializer has panicked");
 
-        let reply = panic::catch_unwind(AssertUnwindSafe(|| (self.sim_gen)(&request.cfg)))
-            .map_err(|payload| {
-                let panic_msg: Option<&str> = if let Some(s) = payload.downcast_ref::<&str>() {
-                    Some(s)
-                } else if let Some(s) = payload.downcast_ref::<String>() {
-                    Some(s)
-                } else {
-                    None
-                };
-
-                let error_msg = if let Some(panic_msg) = panic_msg {
+            to_error(ErrorCode::InitializerPanic, error_msg)
+        })
+        .and_then(|res| {
+            res.map_err(|e| {
+                let err = to_error(
+                    ErrorCode::InvalidMessage,
                     format!(
-                        "the simulation initializer has panicked with the message `{}`",
-                        panic_msg
-                    )
-                } else {
-                    String::from("the simulation initializer has panicked")
-                };
-
-                to_error(ErrorCode::InitializerPanic, error_msg)
+                        "the initializer configuration could not be deserialized: {}",
+                        e
+                    ),
+                );
+                error!(error = %e, "the initializer configuration could not be deserialized");
+                err
             })
-            .and_then(|res| {
-                res.map_err(|e| {
-                    to_error(
-                        ErrorCode::InvalidMessage,
-                        format!(
-                            "the initializer configuration could not be deserialized: {}",
-                            e
-                        ),
-                    )
+            .and_then(|init_result| {
+                init_result.map_err(|e| {
+                    error!(error = %e, "the simulation initializer returned an error");
+                    map_simulation_error(e)
                 })
-                .and_then(|init_result| init_result.map_err(map_simulation_error))
-            });
+            })
+        });
 
         let (reply, bench) = match reply {
-            Ok((mut sim_init, mut registry)) => {
+            Ok((mut sim_init, mut registry, snapshots)) => {
                 registry
                     .event_source_registry
                     .register_scheduler(&mut sim_init.scheduler_registry());
+                let model_init_span = info_span!("model_init");
+                let _enter = model_init_span.enter();
                 match sim_init.init(start_time) {
-                    Ok(simu) => (init_reply::Result::Empty(()), Some((simu, registry))),
-                    Err(e) => (
-                        init_reply::Result::Error(to_error(
-                            ErrorCode::InitializerPanic,
-                            &format!("the simulation initializer has panicked: {}", e),
-                        )),
-                        None,
-                    ),
+                    Ok(simu) => {
+                        let mut initiators = InitiatorRegistry::new();
+                        for make_initiator in &self.initiator_factories {
+                            initiators.register_boxed(make_initiator());
+                        }
+                        match initiators.spawn_all(&registry) {
+                            Ok(guard) => (
+                                init_reply::Result::Empty(()),
+                                Some((simu, registry, guard, snapshots)),
+                            ),
+                            Err(e) => {
+                                error!(error = %e, "could not spawn the bench's initiators");
+                                (
+                                    init_reply::Result::Error(to_error(
+                                        ErrorCode::InitializerPanic,
+                                        &format!("could not spawn the bench's initiators: {}", e),
+                                    )),
+                                    None,
+                                )
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        error!(error = %e, "the simulation initializer has panicked");
+                        (
+                            init_reply::Result::Error(to_error(
+                                ErrorCode::InitializerPanic,
+                                &format!("the simulation initializer has panicked: {}", e),
+                            )),
+                            None,
+                        )
+                    }
                 }
             }
             Err(e) => (init_reply::Result::Error(e), None),
         };
 
+        span.record("elapsed_ms", started_at.elapsed().as_millis());
+
         (
             InitReply {
                 result: Some(reply),
@@ -0,0 +1,4 @@
+//! Remote service endpoints.
+
+pub(crate) mod freeze_service;
+pub(crate) mod init_service;